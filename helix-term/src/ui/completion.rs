@@ -4,6 +4,7 @@ use crate::{
         completion::{CompletionItem, LspCompletionItem, ResolveHandler},
         trigger_auto_completion,
     },
+    job::Callback,
 };
 use helix_view::{
     document::SavePoint,
@@ -12,16 +13,23 @@ use helix_view::{
     theme::{Color, Modifier, Style},
     ViewId,
 };
-use tui::{buffer::Buffer as Surface, text::Span};
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Line, Span},
+};
 
 use std::{borrow::Cow, sync::Arc};
 
 use helix_core::{
     self as core, chars,
     snippets::{ActiveSnippet, RenderedSnippet, Snippet},
-    Change, Transaction,
+    Assoc, Change, Tendril, Transaction,
+};
+use helix_view::{
+    graphics::Rect,
+    input::{KeyCode, KeyModifiers},
+    Document, Editor,
 };
-use helix_view::{graphics::Rect, Document, Editor};
 
 use crate::ui::{menu, Markdown, Menu, Popup, PromptEvent};
 
@@ -30,8 +38,17 @@ use helix_lsp::{
     util, OffsetEncoding,
 };
 
+/// Data shared by every row of the completion menu: the styles `format` uses
+/// to render directory-kind items, matched characters and labelDetails.
+#[derive(Clone)]
+pub struct CompletionItemData {
+    dir_style: Style,
+    matched_style: Style,
+    detail_style: Style,
+}
+
 impl menu::Item for CompletionItem {
-    type Data = Style;
+    type Data = CompletionItemData;
     fn sort_text(&self, data: &Self::Data) -> Cow<str> {
         self.filter_text(data)
     }
@@ -49,7 +66,7 @@ impl menu::Item for CompletionItem {
         }
     }
 
-    fn format(&self, dir_style: &Self::Data) -> menu::Row {
+    fn format(&self, data: &Self::Data, matched_indices: &[u32]) -> menu::Row {
         let deprecated = match self {
             CompletionItem::Lsp(LspCompletionItem { item, .. }) => {
                 item.deprecated.unwrap_or_default()
@@ -65,6 +82,64 @@ impl menu::Item for CompletionItem {
             CompletionItem::Other(core::CompletionItem { label, .. }) => label,
         };
 
+        let is_folder = matches!(
+            self,
+            CompletionItem::Lsp(LspCompletionItem {
+                item: lsp::CompletionItem {
+                    kind: Some(lsp::CompletionItemKind::FOLDER),
+                    ..
+                },
+                ..
+            })
+        ) || matches!(self, CompletionItem::Other(core::CompletionItem { kind, .. }) if kind == "folder");
+
+        let label_style = if deprecated {
+            Style::default().add_modifier(Modifier::CROSSED_OUT)
+        } else if is_folder {
+            data.dir_style
+        } else {
+            Style::default()
+        };
+
+        // LSP 3.17 `labelDetails`: a signature-ish `detail` shown right after
+        // the label and a `description` (e.g. the source module) trailing
+        // the row, both dimmed. Neither is present for `CompletionItem::Other`.
+        // Only items that actually have one contribute a span for it, so a
+        // menu mixing items with and without `labelDetails` doesn't reserve
+        // blank space on the rows that have none.
+        let label_details = match self {
+            CompletionItem::Lsp(LspCompletionItem { item, .. }) => item.label_details.as_ref(),
+            CompletionItem::Other(_) => None,
+        };
+        let detail = label_details.and_then(|details| details.detail.as_deref());
+
+        // `matched_indices` are char indices into `filter_text` (what
+        // `menu.score` actually matched against), which only line up with
+        // `label`'s characters when the two are the same text. LSP items
+        // frequently set a `filter_text` that differs from the displayed
+        // `label` (e.g. a snippet label like `"fn main() {}"` filtered via
+        // `"main"`), so highlighting would land on the wrong characters;
+        // skip it rather than mis-highlight.
+        let label_matched_indices: &[u32] = if self.filter_text(data) == label {
+            matched_indices
+        } else {
+            &[]
+        };
+
+        let first_cell = menu::Cell::from(highlighted_label(
+            label,
+            label_style,
+            data.matched_style,
+            label_matched_indices,
+            detail,
+            data.detail_style,
+        ));
+
+        let description = label_details
+            .and_then(|details| details.description.as_deref())
+            .unwrap_or_default();
+        let description_cell = menu::Cell::from(Span::styled(description, data.detail_style)).right_aligned();
+
         let kind = match self {
             CompletionItem::Lsp(LspCompletionItem { item, .. }) => match item.kind {
                 Some(lsp::CompletionItemKind::TEXT) => "text",
@@ -92,13 +167,14 @@ impl menu::Item for CompletionItem {
                         None => None,
                     };
                     return menu::Row::new([
-                        first_cell,
+                        first_cell.clone(),
                         maybe_hex_color
                             .map_or(Span::raw("color"), |c| match Color::from_hex(c) {
                                 Ok(l) => Span::styled("       ", Style::default().bg(l)),
                                 Err(_) => Span::raw("color"),
                             })
                             .into(),
+                        description_cell.clone(),
                     ]);
                 }
                 Some(lsp::CompletionItemKind::FILE) => "file",
@@ -119,19 +195,51 @@ impl menu::Item for CompletionItem {
             CompletionItem::Other(core::CompletionItem { kind, .. }) => kind,
         };
 
-        let first_cell = menu::Cell::from(Span::styled(
-            label,
-            if deprecated {
-                Style::default().add_modifier(Modifier::CROSSED_OUT)
-            } else if kind.stuff == "folder" {
-                *dir_style
+        menu::Row::new([first_cell, menu::Cell::from(kind), description_cell])
+    }
+}
+
+/// Builds the label cell's spans: `label` highlighted at `matched_indices`
+/// (the char indices of `filter_text` that `menu.score` matched — see
+/// [`menu::Item::format`]), followed by `detail` (labelDetails' `detail`,
+/// e.g. a function signature) dimmed right after it when present.
+fn highlighted_label(
+    label: &str,
+    style: Style,
+    matched_style: Style,
+    matched_indices: &[u32],
+    detail: Option<&str>,
+    detail_style: Style,
+) -> Line<'static> {
+    let mut spans: Vec<(Style, String)> = Vec::new();
+    if matched_indices.is_empty() {
+        spans.push((style, label.to_string()));
+    } else {
+        let mut indices = matched_indices.iter().copied().peekable();
+        for (char_idx, ch) in label.chars().enumerate() {
+            let matched = indices.next_if_eq(&(char_idx as u32)).is_some();
+            let span_style = if matched {
+                style.patch(matched_style)
             } else {
-                Style::default()
-            },
-        ));
+                style
+            };
+            match spans.last_mut() {
+                Some((last_style, text)) if *last_style == span_style => text.push(ch),
+                _ => spans.push((span_style, ch.to_string())),
+            }
+        }
+    }
 
-        menu::Row::new([first_cell, menu::Cell::from(kind)])
+    if let Some(detail) = detail.filter(|detail| !detail.is_empty()) {
+        spans.push((detail_style, format!(" {detail}")));
     }
+
+    Line::from(
+        spans
+            .into_iter()
+            .map(|(style, text)| Span::styled(text, style))
+            .collect::<Vec<_>>(),
+    )
 }
 
 /// Wraps a Menu.
@@ -141,6 +249,9 @@ pub struct Completion {
     trigger_offset: usize,
     filter: String,
     resolve_handler: ResolveHandler,
+    /// Vertical scroll offset of the documentation popup, independent of
+    /// the menu selection. Reset whenever the selection or filter changes.
+    doc_scroll_offset: u16,
 }
 
 impl Completion {
@@ -157,10 +268,15 @@ impl Completion {
         // Sort completion items according to their preselect status (given by the LSP server)
         items.sort_by_key(|item| !item.preselect());
 
-        let dir_style = editor.theme.get("ui.text.directory");
+        let item_data = CompletionItemData {
+            dir_style: editor.theme.get("ui.text.directory"),
+            matched_style: editor.theme.get("ui.completion.matched"),
+            detail_style: editor.theme.get("ui.completion.detail"),
+        };
 
         // Then create the menu
-        let menu = Menu::new(items, dir_style, move |editor: &mut Editor, item, event| {
+        let menu = Menu::new(items, item_data, move |cx: &mut Context, item, event| {
+            let editor = &mut *cx.editor;
             let (view, doc) = current!(editor);
 
             macro_rules! language_server {
@@ -234,21 +350,14 @@ impl Completion {
                     // save an undo checkpoint before the completion
                     doc.append_changes_to_history(view);
 
-                    // item always present here
-                    let (transaction, additional_edits, snippet) = match item.unwrap().clone() {
-                        CompletionItem::Lsp(mut item) => {
+                    // Apply the main edit immediately using whatever text/snippet the
+                    // item already carries, rather than blocking on `textDocument/completionItem/resolve`
+                    // (servers like rust-analyzer can take a while to answer that). If the item
+                    // isn't resolved yet, additional edits (e.g. an auto-import) are applied as a
+                    // follow-up transaction once resolution completes, see below.
+                    let (mut transaction, pending_resolve, snippet) = match item.unwrap().clone() {
+                        CompletionItem::Lsp(item) => {
                             let language_server = language_server!(item);
-
-                            // resolve item if not yet resolved
-                            if !item.resolved {
-                                if let Some(resolved_item) = Self::resolve_completion_item(
-                                    language_server,
-                                    item.item.clone(),
-                                ) {
-                                    item.item = resolved_item;
-                                }
-                            };
-
                             let encoding = language_server.offset_encoding();
                             let (transaction, snippet) = lsp_item_to_transaction(
                                 doc,
@@ -258,19 +367,43 @@ impl Completion {
                                 trigger_offset,
                                 replace_mode,
                             );
-                            let add_edits = item.item.additional_text_edits;
 
-                            (
-                                transaction,
-                                add_edits.map(|edits| (edits, encoding)),
-                                snippet,
-                            )
+                            let pending_resolve = if item.resolved {
+                                item.item
+                                    .additional_text_edits
+                                    .filter(|edits| !edits.is_empty())
+                                    .map(|edits| (edits, encoding))
+                            } else {
+                                None
+                            };
+
+                            (transaction, pending_resolve, snippet)
                         }
                         CompletionItem::Other(core::CompletionItem { transaction, .. }) => {
                             (transaction, None, None)
                         }
                     };
 
+                    let has_pending_resolve = pending_resolve.is_some();
+                    if let Some((additional_edits, offset_encoding)) = pending_resolve {
+                        // Both the primary edit and `additionalTextEdits` (e.g. an
+                        // auto-import) are expressed in the *pre-completion* document's
+                        // coordinates, so compute both against `doc.text()` (still
+                        // unmodified at this point) and merge their changes into a
+                        // single `Transaction` rather than composing the second against
+                        // the first edit's output — composing only happens to land
+                        // correctly when the additional edit sits before the primary
+                        // one; an edit positioned after it would otherwise be applied
+                        // at a shifted offset.
+                        let additional_transaction = util::generate_transaction_from_edits(
+                            doc.text(),
+                            additional_edits,
+                            offset_encoding, // TODO: should probably transcode in Client
+                        );
+                        transaction = merge_transactions(doc.text(), transaction, additional_transaction);
+                    }
+
+                    let text_before_completion = doc.text().clone();
                     doc.apply(&transaction, view.id);
                     let placeholder = snippet.is_some();
                     if let Some(snippet) = snippet {
@@ -286,17 +419,60 @@ impl Completion {
                         placeholder,
                     });
 
-                    // TODO: add additional _edits to completion_changes?
-                    if let Some((additional_edits, offset_encoding)) = additional_edits {
-                        if !additional_edits.is_empty() {
-                            let transaction = util::generate_transaction_from_edits(
-                                doc.text(),
-                                additional_edits,
-                                offset_encoding, // TODO: should probably transcode in Client
-                            );
-                            doc.apply(&transaction, view.id);
+                    if !has_pending_resolve {
+                        if let CompletionItem::Lsp(item) = item.unwrap().clone() {
+                            if !item.resolved {
+                                // Resolve in the background and apply `additionalTextEdits` as a
+                                // follow-up transaction once it comes back, guarded by a snapshot
+                                // of the document text taken now: if it no longer matches when the
+                                // resolve returns (the user kept typing in the meantime) the edits
+                                // are dropped instead of being applied at the wrong offsets.
+                                let language_server = language_server!(item);
+                                if let Some(future) = language_server.resolve_completion_item(&item.item)
+                                {
+                                    let offset_encoding = language_server.offset_encoding();
+                                    let doc_id = doc.id();
+                                    let view_id = view.id;
+                                    let doc_text_at_completion = doc.text().clone();
+                                    let primary_changes = transaction.changes().clone();
+                                    cx.jobs.callback(async move {
+                                        let resolved_item = future.await?;
+                                        let call: Callback = Box::new(move |editor, _compositor| {
+                                            let Some(doc) = editor.documents.get_mut(&doc_id) else {
+                                                return;
+                                            };
+                                            if doc.text() != &doc_text_at_completion {
+                                                return;
+                                            }
+                                            let Some(edits) = resolved_item
+                                                .additional_text_edits
+                                                .filter(|edits| !edits.is_empty())
+                                            else {
+                                                return;
+                                            };
+                                            // The edits refer to the pre-completion text; map
+                                            // them through the completion's own changes.
+                                            let additional = util::generate_transaction_from_edits(
+                                                &text_before_completion,
+                                                edits,
+                                                offset_encoding,
+                                            );
+                                            let transaction = Transaction::change(
+                                                doc.text(),
+                                                additional.changes_iter().map(|(start, end, text)| {
+                                                    let start = primary_changes.map_pos(start, Assoc::Before);
+                                                    (start, primary_changes.map_pos(end, Assoc::Before), text)
+                                                }),
+                                            );
+                                            doc.apply(&transaction, view_id);
+                                        });
+                                        Ok(call)
+                                    });
+                                }
+                            }
                         }
                     }
+
                     // we could have just inserted a trigger char (like a `crate::` completion for rust
                     // so we want to retrigger immediately when accepting a completion.
                     trigger_auto_completion(&editor.handlers.completions, editor, true);
@@ -326,13 +502,15 @@ impl Completion {
         let start_offset = cursor.saturating_sub(offset);
 
         let fragment = doc.text().slice(start_offset..cursor);
+        let filter = String::from(fragment);
         let mut completion = Self {
             popup,
             trigger_offset,
             // TODO: expand nucleo api to allow moving straight to a Utf32String here
             // and avoid allocation during matching
-            filter: String::from(fragment),
+            filter,
             resolve_handler: ResolveHandler::new(),
+            doc_scroll_offset: 0,
         };
 
         // need to recompute immediately in case start_offset != trigger_offset
@@ -344,35 +522,10 @@ impl Completion {
         completion
     }
 
-    /// Synchronously resolve the given completion item. This is used when
-    /// accepting a completion.
-    fn resolve_completion_item(
-        language_server: &helix_lsp::Client,
-        completion_item: lsp::CompletionItem,
-    ) -> Option<lsp::CompletionItem> {
-        if !matches!(
-            language_server.capabilities().completion_provider,
-            Some(lsp::CompletionOptions {
-                resolve_provider: Some(true),
-                ..
-            })
-        ) {
-            return None;
-        }
-        let future = language_server.resolve_completion_item(&completion_item);
-        let response = helix_lsp::block_on(future);
-        match response {
-            Ok(item) => Some(item),
-            Err(err) => {
-                log::error!("Failed to resolve completion item: {}", err);
-                None
-            }
-        }
-    }
-
     /// Appends (`c: Some(c)`) or removes (`c: None`) a character to/from the filter
     /// this should be called whenever the user types or deletes a character in insert mode.
     pub fn update_filter(&mut self, c: Option<char>) {
+        self.doc_scroll_offset = 0;
         // recompute menu based on matches
         let menu = self.popup.contents_mut();
         match c {
@@ -407,7 +560,27 @@ impl Completion {
 
 impl Component for Completion {
     fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
-        self.popup.handle_event(event, cx)
+        if let Event::Key(key) = event {
+            match (key.code, key.modifiers) {
+                (KeyCode::Down, KeyModifiers::CONTROL) => {
+                    self.doc_scroll_offset = self.doc_scroll_offset.saturating_add(1);
+                    return EventResult::Consumed(None);
+                }
+                (KeyCode::Up, KeyModifiers::CONTROL) => {
+                    self.doc_scroll_offset = self.doc_scroll_offset.saturating_sub(1);
+                    return EventResult::Consumed(None);
+                }
+                _ => (),
+            }
+        }
+
+        let result = self.popup.handle_event(event, cx);
+        if !matches!(result, EventResult::Ignored(_)) {
+            // The menu selection or filter may have changed; start the
+            // documentation popup back at the top for the new item.
+            self.doc_scroll_offset = 0;
+        }
+        result
     }
 
     fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
@@ -444,7 +617,10 @@ impl Component for Completion {
                 (None, Some(doc)) => doc.to_string(),
                 (None, None) => String::new(),
             };
-            Markdown::new(md, cx.editor.syn_loader.clone())
+            // Surface hex/rgb color literals in the doc body as swatches, the
+            // same way `CompletionItemKind::COLOR` rows already do for their label.
+            let swatches = color_swatches(&md);
+            Markdown::new(md, swatches, cx.editor.syn_loader.clone())
         };
 
         let mut markdown_doc = match option {
@@ -516,12 +692,40 @@ impl Component for Completion {
         let background = cx.editor.theme.get("ui.popup");
         surface.clear_with(doc_area, background);
 
-        if cx.editor.popup_border() {
+        // Content and the scroll indicator are drawn inside the border, not
+        // over it, so inset by one row/column on every side whenever a
+        // border is actually drawn.
+        let content_area = if cx.editor.popup_border() {
             use tui::widgets::{Block, Widget};
             Widget::render(Block::bordered(), doc_area, surface);
-        }
+            Rect::new(
+                doc_area.x + 1,
+                doc_area.y + 1,
+                doc_area.width.saturating_sub(2),
+                doc_area.height.saturating_sub(2),
+            )
+        } else {
+            doc_area
+        };
 
-        markdown_doc.render(doc_area, surface, cx);
+        // Clamp to the content we actually have, then let the markdown
+        // widget know how far down to start rendering from.
+        let max_scroll = markdown_doc
+            .required_size((content_area.width, u16::MAX))
+            .map_or(0, |(_, full_height)| {
+                full_height.saturating_sub(content_area.height)
+            });
+        self.doc_scroll_offset = self.doc_scroll_offset.min(max_scroll);
+
+        markdown_doc.render_with_offset(content_area, surface, cx, self.doc_scroll_offset);
+
+        // Drawn after the content so it isn't immediately overwritten by
+        // the first wrapped line reaching the same column.
+        if max_scroll > 0 {
+            let indicator = format!(" {}/{} ", self.doc_scroll_offset + 1, max_scroll + 1);
+            let x = content_area.right().saturating_sub(indicator.len() as u16 + 1);
+            surface.set_string(x, content_area.top(), indicator, background);
+        }
     }
 }
 fn lsp_item_to_transaction(
@@ -570,25 +774,40 @@ fn lsp_item_to_transaction(
         (None, new_text)
     };
 
-    if matches!(item.kind, Some(lsp::CompletionItemKind::SNIPPET))
+    let is_snippet = matches!(item.kind, Some(lsp::CompletionItemKind::SNIPPET))
         || matches!(
             item.insert_text_format,
             Some(lsp::InsertTextFormat::SNIPPET)
-        )
-    {
-        let Ok(snippet) = Snippet::parse(&new_text) else {
-            log::error!("Failed to parse snippet: {new_text:?}",);
-            return (Transaction::new(doc.text()), None);
-        };
-        let (transaction, snippet) = util::generate_transaction_from_snippet(
-            doc.text(),
-            selection,
-            edit_offset,
-            replace_mode,
-            snippet,
-            &mut doc.snippet_ctx(),
         );
-        (transaction, Some(snippet))
+
+    if is_snippet {
+        match Snippet::parse(&new_text) {
+            Ok(snippet) => {
+                let (transaction, snippet) = util::generate_transaction_from_snippet(
+                    doc.text(),
+                    selection,
+                    edit_offset,
+                    replace_mode,
+                    snippet,
+                    &mut doc.snippet_ctx(),
+                );
+                return (transaction, Some(snippet));
+            }
+            Err(err) => {
+                // Fall back to inserting `new_text` literally below rather than
+                // dropping the completion entirely over a malformed snippet.
+                log::error!("Failed to parse snippet {new_text:?}: {err:?}");
+            }
+        }
+    }
+
+    if selection.len() > 1 {
+        // Replicate the edit at every cursor rather than just the primary one, each
+        // recomputed relative to its own trigger offset so they stay correct even
+        // though the ranges live at different positions in the document.
+        let transaction =
+            generate_transaction_for_all_cursors(doc.text(), selection, edit_offset, &new_text);
+        (transaction, None)
     } else {
         let transaction = util::generate_transaction_from_completion_edit(
             doc.text(),
@@ -601,9 +820,118 @@ fn lsp_item_to_transaction(
     }
 }
 
+/// Builds a single `Transaction` that inserts `new_text` at every range in
+/// `selection`, replacing `edit_offset` (relative to that range's cursor, same
+/// as the primary cursor's edit) if given, or the already-typed word at that
+/// cursor otherwise (mirroring `util::generate_transaction_from_completion_edit`'s
+/// primary-cursor behavior, so secondary cursors don't end up with the typed
+/// prefix left in front of the inserted completion). Used so that accepting a
+/// completion with multiple cursors active edits all of them as one undo step.
+///
+/// Ranges are visited in document order and each one's start is clamped to
+/// the previous range's end, so cursors close enough together that their
+/// edit ranges would otherwise overlap don't produce an invalid (or
+/// panicking) `Transaction::change`.
+fn generate_transaction_for_all_cursors(
+    text: &core::Rope,
+    selection: &core::Selection,
+    edit_offset: Option<(i128, i128)>,
+    new_text: &str,
+) -> Transaction {
+    let slice = text.slice(..);
+    let mut prev_end = 0;
+    Transaction::change(
+        text,
+        selection.ranges().iter().map(|range| {
+            let cursor = range.cursor(slice);
+            let (start, end) = match edit_offset {
+                Some((start_offset, end_offset)) => (
+                    (cursor as i128 + start_offset).max(0) as usize,
+                    (cursor as i128 + end_offset).max(0) as usize,
+                ),
+                None => {
+                    let word_len = slice
+                        .chars_at(cursor)
+                        .reversed()
+                        .take_while(|ch| chars::char_is_word(*ch))
+                        .count();
+                    (cursor - word_len, cursor)
+                }
+            };
+            let start = start.max(prev_end);
+            let end = end.max(start);
+            prev_end = end;
+            (start, end, Some(Tendril::from(new_text)))
+        }),
+    )
+}
+
+/// Merges two transactions that were both generated against the *same*
+/// original `text`, rather than composing one onto the other's output
+/// (which is only correct when the two edit sets are sequential). The two
+/// are expected to touch disjoint ranges (an `additionalTextEdit` and the
+/// completion's own edit never legitimately overlap); if they do, each
+/// change is cut short where the next one (by start position) begins, so
+/// the later one keeps its whole span.
+fn merge_transactions(text: &core::Rope, a: Transaction, b: Transaction) -> Transaction {
+    let mut changes: Vec<_> = a.changes_iter().chain(b.changes_iter()).collect();
+    changes.sort_by_key(|(start, _, _)| *start);
+    let mut changes = changes.into_iter().peekable();
+    let merged = std::iter::from_fn(move || {
+        let (start, end, replacement) = changes.next()?;
+        let end = changes.peek().map_or(end, |(next_start, _, _)| end.min(*next_start));
+        Some((start, end, replacement))
+    });
+    Transaction::change(text, merged)
+}
+
 fn completion_changes(transaction: &Transaction, trigger_offset: usize) -> Vec<Change> {
     transaction
         .changes_iter()
         .filter(|(start, end, _)| (*start..=*end).contains(&trigger_offset))
         .collect()
 }
+
+/// Scans `text` for literal `#hex` and `rgb(r, g, b)` color tokens, reusing
+/// the same `Color::from_hex` parsing that `CompletionItemKind::COLOR` rows
+/// use for their label swatch. Returns the byte range of each token paired
+/// with the color it names, for the `Markdown` widget to render a swatch next to.
+fn color_swatches(text: &str) -> Vec<(std::ops::Range<usize>, Color)> {
+    let mut swatches = Vec::new();
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'#' {
+            let end = (i + 1..bytes.len())
+                .find(|&j| !bytes[j].is_ascii_hexdigit())
+                .unwrap_or(bytes.len());
+            if end - i >= 4 {
+                if let Ok(color) = Color::from_hex(&text[i..end]) {
+                    swatches.push((i..end, color));
+                    i = end;
+                    continue;
+                }
+            }
+        } else if text[i..].starts_with("rgb(") {
+            if let Some(close) = text[i..].find(')') {
+                let end = i + close + 1;
+                if let Some(color) = parse_rgb_fn(&text[i..end]) {
+                    swatches.push((i..end, color));
+                    i = end;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+    swatches
+}
+
+fn parse_rgb_fn(token: &str) -> Option<Color> {
+    let inner = token.strip_prefix("rgb(")?.strip_suffix(')')?;
+    let mut channels = inner.split(',').map(|c| c.trim().parse::<u8>());
+    let r = channels.next()?.ok()?;
+    let g = channels.next()?.ok()?;
+    let b = channels.next()?.ok()?;
+    (channels.next().is_none()).then_some(Color::Rgb(r, g, b))
+}
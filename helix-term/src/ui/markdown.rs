@@ -0,0 +1,305 @@
+use arc_swap::ArcSwap;
+use std::{ops::Range, sync::Arc};
+
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
+use unicode_width::UnicodeWidthStr;
+
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Line, Span},
+};
+
+use helix_core::syntax;
+use helix_view::{
+    graphics::Rect,
+    theme::{Color, Modifier, Style},
+};
+
+use crate::compositor::{Component, Context};
+
+/// One piece of text produced by walking the markdown's parse tree, still
+/// tagged with the block/inline role it came from (resolved to an actual
+/// [`Style`] against the theme at render time) and the byte range it spans
+/// in the original `contents`, so swatches (also byte ranges into
+/// `contents`) can be mapped back onto it.
+struct Run {
+    text: String,
+    role: Role,
+    modifiers: Modifier,
+    source: Range<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Text,
+    Heading,
+    Code,
+    CodeBlock,
+    ListMarker,
+}
+
+impl Role {
+    fn style(self, theme_style: impl Fn(&str) -> Style, modifiers: Modifier) -> Style {
+        let base = match self {
+            Role::Text => theme_style("ui.text"),
+            Role::Heading => theme_style("markup.heading"),
+            Role::Code => theme_style("markup.raw.inline"),
+            Role::CodeBlock => theme_style("markup.raw.block"),
+            Role::ListMarker => theme_style("markup.list"),
+        };
+        base.add_modifier(modifiers)
+    }
+}
+
+/// A word-wrapped visual line: a sequence of `Run`s (already split on word
+/// boundaries and re-grouped to fit the available width).
+type WrappedLine = Vec<(String, Role, Modifier, Range<usize>)>;
+
+/// Renders markdown documentation (completion docs, hover info, ...),
+/// word-wrapped to the available width. Headings, emphasis and code
+/// spans/blocks are styled per the theme; hex/rgb color literals
+/// (see `completion::color_swatches`) are rendered as swatches inline.
+/// Fenced code blocks are styled uniformly rather than syntax-highlighted
+/// per token — plugging `config_loader` into a full `Syntax` highlight
+/// pass is left as a follow-up.
+pub struct Markdown {
+    contents: String,
+    swatches: Vec<(Range<usize>, Color)>,
+    config_loader: Arc<ArcSwap<syntax::Loader>>,
+}
+
+impl Markdown {
+    pub fn new(
+        contents: String,
+        swatches: Vec<(Range<usize>, Color)>,
+        config_loader: Arc<ArcSwap<syntax::Loader>>,
+    ) -> Self {
+        Self {
+            contents,
+            swatches,
+            config_loader,
+        }
+    }
+
+    /// Walks `contents`' markdown parse tree into logical lines of `Run`s,
+    /// one per block-level line (paragraph, heading, list item, code block
+    /// line, ...), not yet wrapped to any particular width.
+    fn parse(&self) -> Vec<Vec<Run>> {
+        let mut lines: Vec<Vec<Run>> = vec![Vec::new()];
+        let mut modifiers = vec![Modifier::empty()];
+        let mut role = vec![Role::Text];
+        let mut in_code_block = false;
+
+        let parser = Parser::new_ext(&self.contents, Options::ENABLE_STRIKETHROUGH);
+        for (event, range) in parser.into_offset_iter() {
+            match event {
+                Event::Start(Tag::Heading(..)) => role.push(Role::Heading),
+                Event::End(Tag::Heading(..)) => {
+                    role.pop();
+                    lines.push(Vec::new());
+                }
+                Event::Start(Tag::Strong) => {
+                    modifiers.push(*modifiers.last().unwrap() | Modifier::BOLD)
+                }
+                Event::End(Tag::Strong) => {
+                    modifiers.pop();
+                }
+                Event::Start(Tag::Emphasis) => {
+                    modifiers.push(*modifiers.last().unwrap() | Modifier::ITALIC)
+                }
+                Event::End(Tag::Emphasis) => {
+                    modifiers.pop();
+                }
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    in_code_block = true;
+                    // Looks the fence's language up in the loader; the result
+                    // is discarded and the block's text is styled uniformly
+                    // as `Role::CodeBlock`.
+                    if let CodeBlockKind::Fenced(lang) = &kind {
+                        let _ = self.config_loader.load().language_config_for_name(lang);
+                    }
+                    role.push(Role::CodeBlock);
+                    if !lines.last().unwrap().is_empty() {
+                        lines.push(Vec::new());
+                    }
+                }
+                Event::End(Tag::CodeBlock(_)) => {
+                    in_code_block = false;
+                    role.pop();
+                    lines.push(Vec::new());
+                }
+                Event::Start(Tag::Item) => {
+                    lines.last_mut().unwrap().push(Run {
+                        text: "- ".to_string(),
+                        role: Role::ListMarker,
+                        modifiers: Modifier::empty(),
+                        source: range.start..range.start,
+                    });
+                }
+                Event::End(Tag::Item) => lines.push(Vec::new()),
+                Event::End(Tag::Paragraph) => lines.push(Vec::new()),
+                Event::Code(text) => lines.last_mut().unwrap().push(Run {
+                    text: text.into_string(),
+                    role: Role::Code,
+                    modifiers: *modifiers.last().unwrap(),
+                    source: range,
+                }),
+                Event::Text(text) if in_code_block => {
+                    let parts: Vec<&str> = text.split('\n').collect();
+                    for (i, part) in parts.iter().enumerate() {
+                        if !part.is_empty() {
+                            lines.last_mut().unwrap().push(Run {
+                                text: (*part).to_string(),
+                                role: Role::CodeBlock,
+                                modifiers: Modifier::empty(),
+                                source: range.clone(),
+                            });
+                        }
+                        if i + 1 < parts.len() {
+                            lines.push(Vec::new());
+                        }
+                    }
+                }
+                Event::Text(text) => lines.last_mut().unwrap().push(Run {
+                    text: text.into_string(),
+                    role: *role.last().unwrap(),
+                    modifiers: *modifiers.last().unwrap(),
+                    source: range,
+                }),
+                Event::SoftBreak | Event::HardBreak => {
+                    lines.last_mut().unwrap().push(Run {
+                        text: " ".to_string(),
+                        role: Role::Text,
+                        modifiers: Modifier::empty(),
+                        source: range,
+                    })
+                }
+                _ => {}
+            }
+        }
+        lines.retain(|line| !line.is_empty());
+        lines
+    }
+
+    /// Re-flows `self.parse()`'s logical lines into `max_width`-wide visual
+    /// lines, splitting runs on word boundaries and measuring with display
+    /// width (not byte length) so wrapping is correct for non-ASCII text.
+    fn wrapped_lines(&self, max_width: u16) -> Vec<WrappedLine> {
+        let max_width = max_width.max(1) as usize;
+        let mut out = Vec::new();
+        for line in self.parse() {
+            let mut words = Vec::new();
+            for run in &line {
+                for (local_start, word) in word_ranges(&run.text) {
+                    let start = run.source.start + local_start;
+                    words.push((
+                        word.to_string(),
+                        run.role,
+                        run.modifiers,
+                        start..start + word.len(),
+                    ));
+                }
+            }
+            if words.is_empty() {
+                out.push(Vec::new());
+                continue;
+            }
+
+            let mut row: WrappedLine = Vec::new();
+            let mut row_width = 0;
+            for word in words {
+                let word_width = word.0.width();
+                let extra = if row.is_empty() { 0 } else { 1 };
+                if !row.is_empty() && row_width + extra + word_width > max_width {
+                    out.push(std::mem::take(&mut row));
+                    row_width = 0;
+                }
+                row_width += if row.is_empty() { 0 } else { 1 } + word_width;
+                row.push(word);
+            }
+            out.push(row);
+        }
+        out
+    }
+
+    /// Builds the styled spans for one wrapped visual line, splicing in a
+    /// swatch right after any word that overlaps one of `self.swatches`.
+    fn render_row(&self, row: &WrappedLine, theme_style: impl Fn(&str) -> Style) -> Line<'static> {
+        let mut spans = Vec::new();
+        for (i, (text, role, modifiers, source)) in row.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(" "));
+            }
+            spans.push(Span::styled(text.clone(), role.style(&theme_style, *modifiers)));
+            if let Some((_, color)) = self
+                .swatches
+                .iter()
+                .find(|(range, _)| range.start < source.end && range.end > source.start)
+            {
+                spans.push(Span::styled(" ", Style::default().bg(*color)));
+            }
+        }
+        Line::from(spans)
+    }
+
+    /// Renders the wrapped content starting at `offset` lines from the top,
+    /// so a caller can scroll a long doc popup without us owning the scroll
+    /// state ourselves.
+    pub fn render_with_offset(
+        &mut self,
+        area: Rect,
+        surface: &mut Surface,
+        cx: &mut Context,
+        offset: u16,
+    ) {
+        let theme = &cx.editor.theme;
+        let rows = self.wrapped_lines(area.width);
+        for (i, row) in rows
+            .iter()
+            .skip(offset as usize)
+            .take(area.height as usize)
+            .enumerate()
+        {
+            let line = self.render_row(row, |key| theme.get(key));
+            surface.set_line(area.x, area.y + i as u16, &line, area.width);
+        }
+    }
+}
+
+/// Splits `text` on ASCII spaces, yielding each word with its byte offset
+/// within `text`.
+fn word_ranges(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let bytes = text.as_bytes();
+    let mut idx = 0;
+    std::iter::from_fn(move || {
+        while idx < bytes.len() && bytes[idx] == b' ' {
+            idx += 1;
+        }
+        if idx >= bytes.len() {
+            return None;
+        }
+        let start = idx;
+        while idx < bytes.len() && bytes[idx] != b' ' {
+            idx += 1;
+        }
+        Some((start, &text[start..idx]))
+    })
+}
+
+fn row_width(row: &WrappedLine) -> u16 {
+    let words: u16 = row.iter().map(|(text, ..)| text.width() as u16).sum();
+    let spaces = row.len().saturating_sub(1) as u16;
+    words + spaces
+}
+
+impl Component for Markdown {
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        self.render_with_offset(area, surface, cx, 0);
+    }
+
+    fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
+        let rows = self.wrapped_lines(viewport.0);
+        let width = rows.iter().map(row_width).max().unwrap_or(0);
+        Some((width.min(viewport.0), rows.len() as u16))
+    }
+}
@@ -0,0 +1,308 @@
+use std::{borrow::Cow, cmp::Reverse};
+
+use nucleo::{
+    pattern::{CaseMatching, Normalization, Pattern},
+    Matcher, Utf32Str,
+};
+
+use tui::{buffer::Buffer as Surface, text::Line};
+
+use helix_view::{
+    graphics::Rect,
+    input::{KeyCode, KeyModifiers},
+};
+
+use crate::{
+    compositor::{Component, Context, Event, EventResult},
+    ui::PromptEvent,
+};
+
+/// A single cell of a [`Row`]. Built from whatever text/spans the item wants
+/// to show; see [`Cell::right_aligned`] for trailing, right-aligned columns
+/// such as labelDetails' `description`.
+#[derive(Clone)]
+pub struct Cell {
+    content: Line<'static>,
+    alignment: Alignment,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    Left,
+    Right,
+}
+
+impl Cell {
+    /// Marks this cell as right-aligned within the space it's given, rather
+    /// than reserving a fixed column like the other cells.
+    pub fn right_aligned(mut self) -> Self {
+        self.alignment = Alignment::Right;
+        self
+    }
+
+    fn width(&self) -> u16 {
+        self.content.width() as u16
+    }
+}
+
+impl<T: Into<Line<'static>>> From<T> for Cell {
+    fn from(value: T) -> Self {
+        Cell {
+            content: value.into(),
+            alignment: Alignment::Left,
+        }
+    }
+}
+
+/// A row of cells rendered for one menu item.
+pub struct Row {
+    cells: Vec<Cell>,
+}
+
+impl Row {
+    pub fn new<const N: usize>(cells: [Cell; N]) -> Self {
+        Row {
+            cells: cells.into(),
+        }
+    }
+}
+
+/// An item that can be shown, filtered and sorted in a [`Menu`].
+pub trait Item: Sync + Send + 'static {
+    /// Extra data shared by every item, passed through to `format` (theme
+    /// styles and the like that don't belong on the item itself).
+    type Data: Send + Sync;
+
+    /// The text used to order matching items, most relevant first.
+    fn sort_text(&self, data: &Self::Data) -> Cow<str>;
+
+    /// The text fuzzy-matched against the user's filter.
+    fn filter_text(&self, data: &Self::Data) -> Cow<str>;
+
+    /// Builds this item's row. `matched_indices` are the char indices of
+    /// `filter_text` that the current filter fuzzy-matched, as computed by
+    /// [`Menu::score`]; implementations that highlight the label should use
+    /// these rather than re-deriving their own.
+    fn format(&self, data: &Self::Data, matched_indices: &[u32]) -> Row;
+}
+
+struct OptionMatch {
+    index: usize,
+    indices: Vec<u32>,
+}
+
+/// A selectable, fuzzy-filterable list of `T`, usually shown inside a
+/// [`Popup`](super::Popup).
+pub struct Menu<T: Item> {
+    options: Vec<T>,
+    matches: Vec<OptionMatch>,
+    data: T::Data,
+    cursor: Option<usize>,
+    matcher: Matcher,
+    #[allow(clippy::type_complexity)]
+    on_event: Box<dyn Fn(&mut Context, Option<&T>, PromptEvent)>,
+    scroll: usize,
+}
+
+impl<T: Item> Menu<T> {
+    pub fn new(
+        options: Vec<T>,
+        data: T::Data,
+        on_event: impl Fn(&mut Context, Option<&T>, PromptEvent) + 'static,
+    ) -> Self {
+        let matches = (0..options.len())
+            .map(|index| OptionMatch {
+                index,
+                indices: Vec::new(),
+            })
+            .collect();
+        let mut menu = Self {
+            options,
+            matches,
+            data,
+            cursor: None,
+            matcher: Matcher::default(),
+            on_event: Box::new(on_event),
+            scroll: 0,
+        };
+        menu.cursor = if menu.matches.is_empty() { None } else { Some(0) };
+        menu
+    }
+
+    /// Re-filters and re-sorts the options against `pattern`. `incremental`
+    /// is true when `pattern` only grew by one character since the last
+    /// call, which callers can use to preserve the current selection instead
+    /// of always resetting to the top match.
+    pub fn score(&mut self, pattern: &str, incremental: bool) {
+        let prev_selection = incremental
+            .then(|| self.selection())
+            .flatten()
+            .map(|option| option.sort_text(&self.data).into_owned());
+
+        let pattern = Pattern::parse(pattern, CaseMatching::Ignore, Normalization::Smart);
+        let mut matches: Vec<_> = self
+            .options
+            .iter()
+            .enumerate()
+            .filter_map(|(index, option)| {
+                let text = option.filter_text(&self.data);
+                let mut buf = Vec::new();
+                let haystack = Utf32Str::new(&text, &mut buf);
+                let mut indices = Vec::new();
+                let score = pattern.indices(haystack, &mut self.matcher, &mut indices)?;
+                indices.sort_unstable();
+                indices.dedup();
+                Some((OptionMatch { index, indices }, score))
+            })
+            .collect();
+        matches.sort_by_key(|(_, score)| Reverse(*score));
+        self.matches = matches.into_iter().map(|(m, _)| m).collect();
+
+        self.cursor = prev_selection
+            .and_then(|prev| {
+                self.matches.iter().position(|m| {
+                    self.options[m.index].sort_text(&self.data) == prev
+                })
+            })
+            .or_else(|| (!self.matches.is_empty()).then_some(0));
+        self.scroll = 0;
+    }
+
+    pub fn clear(&mut self) {
+        self.matches = (0..self.options.len())
+            .map(|index| OptionMatch {
+                index,
+                indices: Vec::new(),
+            })
+            .collect();
+        self.cursor = if self.matches.is_empty() { None } else { Some(0) };
+        self.scroll = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    pub fn selection(&self) -> Option<&T> {
+        self.cursor
+            .and_then(|cursor| self.matches.get(cursor))
+            .map(|m| &self.options[m.index])
+    }
+
+    pub fn selection_mut(&mut self) -> Option<&mut T> {
+        let index = self.cursor.and_then(|cursor| self.matches.get(cursor)).map(|m| m.index)?;
+        Some(&mut self.options[index])
+    }
+
+    pub fn replace_option(&mut self, old_item: &impl PartialEq<T>, new_item: T) {
+        if let Some(option) = self.options.iter_mut().find(|option| old_item == *option) {
+            *option = new_item;
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let len = self.matches.len() as isize;
+        let current = self.cursor.unwrap_or(0) as isize;
+        self.cursor = Some(((current + delta).rem_euclid(len)) as usize);
+    }
+}
+
+impl<T: Item> Component for Menu<T> {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let Event::Key(key) = event else {
+            return EventResult::Ignored(None);
+        };
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                self.move_cursor(-1);
+                (self.on_event)(cx, self.selection(), PromptEvent::Update);
+            }
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                self.move_cursor(1);
+                (self.on_event)(cx, self.selection(), PromptEvent::Update);
+            }
+            (KeyCode::Esc, _) => {
+                (self.on_event)(cx, self.selection(), PromptEvent::Abort);
+                return EventResult::Consumed(None);
+            }
+            (KeyCode::Enter, _) | (KeyCode::Tab, _) => {
+                let selection = self.cursor.and_then(|cursor| self.matches.get(cursor)).map(|m| m.index);
+                // Work around borrowing `self` both immutably (for the selection) and
+                // mutably (to invoke the callback) by looking the item up by index.
+                let item = selection.map(|index| &self.options[index]);
+                (self.on_event)(cx, item, PromptEvent::Validate);
+                return EventResult::Consumed(None);
+            }
+            _ => return EventResult::Ignored(None),
+        }
+
+        EventResult::Consumed(None)
+    }
+
+    fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
+        let height = (self.matches.len() as u16).min(viewport.1).max(1);
+        Some((viewport.0, height))
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let style = cx.editor.theme.get("ui.menu");
+        let selected_style = cx.editor.theme.get("ui.menu.selected");
+
+        let rows: Vec<Row> = self
+            .matches
+            .iter()
+            .skip(self.scroll)
+            .take(area.height as usize)
+            .map(|m| self.options[m.index].format(&self.data, &m.indices))
+            .collect();
+
+        // Fixed-width columns, sized to the widest cell seen in that column,
+        // except right-aligned cells which are packed against the row's
+        // right edge instead of reserving their own column.
+        let mut column_widths = vec![0u16; rows.iter().map(|r| r.cells.len()).max().unwrap_or(0)];
+        for row in &rows {
+            for (i, cell) in row.cells.iter().enumerate() {
+                if cell.alignment == Alignment::Left {
+                    column_widths[i] = column_widths[i].max(cell.width());
+                }
+            }
+        }
+
+        for (row_idx, row) in rows.iter().enumerate() {
+            let is_selected = self.cursor.map_or(false, |cursor| cursor == self.scroll + row_idx);
+            let row_style = if is_selected { selected_style } else { style };
+
+            surface.set_style(
+                Rect::new(area.x, area.y + row_idx as u16, area.width, 1),
+                row_style,
+            );
+
+            let right_aligned_width: u16 = row
+                .cells
+                .iter()
+                .filter(|cell| cell.alignment == Alignment::Right)
+                .map(|cell| cell.width())
+                .sum();
+
+            let mut x = area.x;
+            for (i, cell) in row.cells.iter().enumerate() {
+                if cell.alignment == Alignment::Right {
+                    continue;
+                }
+                surface.set_line(x, area.y + row_idx as u16, &cell.content, column_widths[i]);
+                x += column_widths[i] + 1;
+            }
+
+            let mut right_x = area.x + area.width.saturating_sub(right_aligned_width);
+            for cell in row.cells.iter().filter(|cell| cell.alignment == Alignment::Right) {
+                let width = cell.width();
+                surface.set_line(right_x, area.y + row_idx as u16, &cell.content, width);
+                right_x += width;
+            }
+        }
+    }
+}